@@ -1,6 +1,10 @@
 use std;
 
 use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::ErrorPlus;
 
 /// Return type of wait_for_one_to_complete indicating which future completed before the other.
 pub enum FirstOrSecond<A, B> {
@@ -25,3 +29,215 @@ where
         Either::Right((value_2, _)) => FirstOrSecond::Second(value_2),
     }
 }
+
+/// Wait for the first of any number of futures to complete, returning its index
+/// in `futures` along with its output. The rest are dropped, just like
+/// `wait_for_one_to_complete` but for an arbitrary, heterogeneous-at-runtime set.
+pub async fn wait_for_any<T>(futures: Vec<Pin<Box<dyn Future<Output = T>>>>) -> (usize, T) {
+    let (value, index, _still_running) = futures::future::select_all(futures).await;
+    (index, value)
+}
+
+/// Race `fut` against a deadline, returning `Err(Elapsed)` if `dur` passes first.
+/// A thin wrapper over `tokio::time::timeout` so the many timeout/cancel patterns
+/// in the radio tests don't each re-derive the same `FirstOrSecond` match by hand.
+pub async fn with_timeout<Fut, Out>(
+    fut: Fut,
+    dur: Duration,
+) -> Result<Out, tokio::time::error::Elapsed>
+where
+    Fut: Future<Output = Out>,
+{
+    tokio::time::timeout(dur, fut).await
+}
+
+/// How long to wait between attempts of a `retry_until`, and how many to allow.
+#[derive(Debug, Clone)]
+pub enum BackoffPolicy {
+    /// Always wait the same amount of time between attempts.
+    Constant {
+        delay: Duration,
+        max_attempts: Option<u32>,
+    },
+    /// Wait `base_delay * multiplier^(attempt - 1)`, capped at `max_delay`.
+    Exponential {
+        base_delay: Duration,
+        multiplier: f64,
+        max_delay: Duration,
+        max_attempts: Option<u32>,
+    },
+}
+
+impl BackoffPolicy {
+    pub fn constant(delay: Duration) -> Self {
+        BackoffPolicy::Constant {
+            delay,
+            max_attempts: None,
+        }
+    }
+
+    pub fn exponential(base_delay: Duration, multiplier: f64, max_delay: Duration) -> Self {
+        BackoffPolicy::Exponential {
+            base_delay,
+            multiplier,
+            max_delay,
+            max_attempts: None,
+        }
+    }
+
+    /// Give up and return the last error after this many failed attempts.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        match &mut self {
+            BackoffPolicy::Constant {
+                max_attempts: m, ..
+            }
+            | BackoffPolicy::Exponential {
+                max_attempts: m, ..
+            } => *m = Some(max_attempts),
+        }
+        self
+    }
+
+    fn max_attempts(&self) -> Option<u32> {
+        match self {
+            BackoffPolicy::Constant { max_attempts, .. } => *max_attempts,
+            BackoffPolicy::Exponential { max_attempts, .. } => *max_attempts,
+        }
+    }
+
+    fn delay_for(&self, attempt: u32) -> Duration {
+        match self {
+            BackoffPolicy::Constant { delay, .. } => *delay,
+            BackoffPolicy::Exponential {
+                base_delay,
+                multiplier,
+                max_delay,
+                ..
+            } => {
+                // Clamp in f64 space before converting: an uncapped `scaled` can
+                // overflow or hit infinity for large `attempt`, and
+                // Duration::from_secs_f64 panics on that instead of saturating.
+                let scaled = base_delay.as_secs_f64() * multiplier.powi(attempt as i32 - 1);
+                Duration::from_secs_f64(scaled.min(max_delay.as_secs_f64()))
+            }
+        }
+    }
+}
+
+/// Retry a fallible state transition until it succeeds, recovering the moved-out
+/// state from `ErrorPlus::other` on each failure and feeding it back into `op`.
+///
+/// This generalizes the "loop calling a transition that returns the radio on
+/// failure" pattern used throughout the radio state machine tests, so callers
+/// don't hand-roll the loop and the sleep/yield handling themselves.
+pub async fn retry_until<S, T, E, Fut, F>(
+    mut state: S,
+    mut op: F,
+    policy: BackoffPolicy,
+) -> Result<T, ErrorPlus<S, E>>
+where
+    F: FnMut(S) -> Fut,
+    Fut: Future<Output = Result<T, ErrorPlus<S, E>>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        match op(state).await {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                attempt += 1;
+                if matches!(policy.max_attempts(), Some(max) if attempt >= max) {
+                    return Err(err);
+                }
+                state = err.other;
+                let delay = policy.delay_for(attempt);
+                if delay.is_zero() {
+                    // Busy-retrying with no delay; yield so we don't starve the runtime.
+                    tokio::task::yield_now().await;
+                } else {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_any_returns_fastest_index_and_value() {
+        let futures: Vec<Pin<Box<dyn Future<Output = u32>>>> = vec![
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                0u32
+            }),
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                1u32
+            }),
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                2u32
+            }),
+        ];
+
+        let (index, value) = wait_for_any(futures).await;
+        assert_eq!(index, 1);
+        assert_eq!(value, 1);
+    }
+
+    #[tokio::test]
+    async fn test_backoff_policy_exponential_delay_caps_at_max() {
+        let policy =
+            BackoffPolicy::exponential(Duration::from_millis(100), 2.0, Duration::from_millis(300));
+
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        // Uncapped this would be 400ms; max_delay caps it at 300ms.
+        assert_eq!(policy.delay_for(3), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_backoff_policy_exponential_delay_never_overflows_duration() {
+        let policy =
+            BackoffPolicy::exponential(Duration::from_millis(100), 2.0, Duration::from_millis(300));
+
+        // `base_delay * multiplier.powi(attempt - 1)` blows past what
+        // Duration::from_secs_f64 can represent (from attempt 69) and on to
+        // f64::INFINITY (from attempt 1025); every
+        // one of these must still come back clamped to max_delay instead of
+        // panicking.
+        for attempt in [100, 1_000, i32::MAX as u32] {
+            assert_eq!(policy.delay_for(attempt), Duration::from_millis(300));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_gives_up_after_max_attempts() {
+        let mut calls = 0u32;
+        let result = retry_until(
+            0u32,
+            |state| {
+                calls += 1;
+                async move {
+                    Err::<u32, _>(ErrorPlus {
+                        error: "nope".to_string(),
+                        other: state + 1,
+                    })
+                }
+            },
+            BackoffPolicy::constant(Duration::ZERO).with_max_attempts(3),
+        )
+        .await;
+
+        assert_eq!(calls, 3);
+        match result {
+            Err(e) => {
+                assert_eq!(e.error, "nope");
+                assert_eq!(e.other, 3);
+            }
+            Ok(_) => panic!("expected retry_until to give up after max_attempts"),
+        }
+    }
+}
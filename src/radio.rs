@@ -1,16 +1,36 @@
+use crate::radio::hal::ReceiveInfo;
 use crate::ErrorPlus;
 use anyhow::Result;
 
-type RadioError<T> = ErrorPlus<T>;
+pub mod hal;
+pub mod manager;
+
+type RadioError<T> = ErrorPlus<T, anyhow::Error>;
+
+/// Maximum payload size this radio's framing supports, in either direction.
+pub const MAX_PACKET_LEN: usize = 256;
+
+/// Valid range for `ConfigureData::power_dbm`.
+pub const MIN_POWER_DBM: i8 = -10;
+pub const MAX_POWER_DBM: i8 = 30;
+
+/// Highest channel number `ConfigureData::channel` and `set_channel` accept.
+pub const MAX_CHANNEL: u8 = 15;
 
 // Define the different state types
 pub struct Configured {
     pub config: ConfigureData,
 }
 
-pub struct Operate;
+pub struct Operate {
+    pub config: ConfigureData,
+}
 
-pub struct Standby;
+pub struct Standby {
+    /// The config in effect the last time this radio was configured, if any,
+    /// so a subsequent `configure` can reuse or diff against it.
+    pub last_config: Option<ConfigureData>,
+}
 
 pub struct Uninitialized;
 
@@ -76,15 +96,28 @@ impl Radio<Uninitialized> {
 
         Ok(Radio {
             data: self.data,
-            state: Standby,
+            state: Standby { last_config: None },
         })
     }
 }
 
-/// Data that might be needed to configure the radio.
-/// Frequencies, power, etc.
-#[derive(Default, Debug)]
-pub struct ConfigureData;
+/// Data needed to configure the radio: frequency, transmit power, and channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfigureData {
+    pub frequency_hz: u64,
+    pub power_dbm: i8,
+    pub channel: u8,
+}
+
+impl Default for ConfigureData {
+    fn default() -> Self {
+        ConfigureData {
+            frequency_hz: 915_000_000,
+            power_dbm: 14,
+            channel: 0,
+        }
+    }
+}
 
 impl Radio<Standby> {
     /// Attempt to configure the radio with the given data
@@ -92,6 +125,40 @@ impl Radio<Standby> {
         self,
         configdata: ConfigureData,
     ) -> Result<Radio<Configured>, RadioError<Self>> {
+        if configdata.frequency_hz == 0 {
+            return Err(RadioError {
+                error: anyhow::anyhow!("frequency must be non-zero"),
+                other: self,
+            });
+        }
+        if !(MIN_POWER_DBM..=MAX_POWER_DBM).contains(&configdata.power_dbm) {
+            return Err(RadioError {
+                error: anyhow::anyhow!(
+                    "transmit power {} dBm out of range [{}, {}]",
+                    configdata.power_dbm,
+                    MIN_POWER_DBM,
+                    MAX_POWER_DBM
+                ),
+                other: self,
+            });
+        }
+        if configdata.channel > MAX_CHANNEL {
+            return Err(RadioError {
+                error: anyhow::anyhow!(
+                    "channel {} exceeds MAX_CHANNEL ({})",
+                    configdata.channel,
+                    MAX_CHANNEL
+                ),
+                other: self,
+            });
+        }
+
+        if let Some(prev) = self.state.last_config {
+            if prev != configdata {
+                println!("Reconfiguring radio: {:?} -> {:?}", prev, configdata);
+            }
+        }
+
         // Perform configuration actions here
         //tokio::time::sleep(Duration::from_secs(1)).await;
 
@@ -111,7 +178,9 @@ impl Radio<Configured> {
 
         Ok(Radio {
             data: self.data,
-            state: Operate,
+            state: Operate {
+                config: self.state.config,
+            },
         })
     }
     // Can go back to standby without error (maybe need error given some other implementation).
@@ -120,25 +189,59 @@ impl Radio<Configured> {
 
         Radio {
             data: self.data,
-            state: Standby,
+            state: Standby {
+                last_config: Some(self.state.config),
+            },
         }
     }
 }
 
 impl Radio<Operate> {
     /// can only send data in operate mode, might fail.
-    pub async fn send_data(&self, _data: &[u8]) -> Result<()> {
+    pub async fn send_data(&self, data: &[u8]) -> Result<()> {
+        if data.len() > MAX_PACKET_LEN {
+            anyhow::bail!(
+                "payload of {} bytes exceeds MAX_PACKET_LEN ({})",
+                data.len(),
+                MAX_PACKET_LEN
+            );
+        }
         //println!("Sending data in operate mode");
         // Perform operate actions here
         Ok(())
     }
+    /// Poll the radio for a received frame. Returns the number of bytes written
+    /// into `buf` plus the signal quality of the receive, or 0 bytes if nothing
+    /// is pending.
+    pub async fn receive(&self, buf: &mut [u8]) -> Result<(usize, ReceiveInfo)> {
+        if buf.len() > MAX_PACKET_LEN {
+            anyhow::bail!(
+                "receive buffer of {} bytes exceeds MAX_PACKET_LEN ({})",
+                buf.len(),
+                MAX_PACKET_LEN
+            );
+        }
+        // Perform operate actions here
+        Ok((0, ReceiveInfo::default()))
+    }
+    /// Retune to a different channel without a full teardown back through Standby.
+    pub async fn set_channel(&mut self, channel: u8) -> Result<()> {
+        if channel > MAX_CHANNEL {
+            anyhow::bail!("channel {} exceeds MAX_CHANNEL ({})", channel, MAX_CHANNEL);
+        }
+        // Perform the actual retune here
+        self.state.config.channel = channel;
+        Ok(())
+    }
     /// Go back to standby
     pub async fn enter_standby(self) -> Radio<Standby> {
         //println!("Entering Standby mode");
         // Perform standby actions here
         Radio {
             data: self.data,
-            state: Standby,
+            state: Standby {
+                last_config: Some(self.state.config),
+            },
         }
     }
 }
@@ -215,22 +318,16 @@ mod tests {
 
     /// Try to enter standby mode continuously until successful.
     /// We write this as a function, we don't pollute the Radio implementation.
-    async fn try_enter_standby_forever(mut radio: Radio<Uninitialized>) -> Radio<Standby> {
-        // Loop as many times as needed to get into standby mode (it might not be ready)
-        loop {
-            // Try to go into standby
-            match radio.standby().await {
-                Ok(radio) => break radio,
-                Err(e) => {
-                    // Bad day, try again
-                    // yield because we don't ever actually await for anything.
-                    // Necessary because we need to allow other tasks to run.
-                    tokio::task::yield_now().await;
-                    // The prior radio is in the error struct, so pull it out and try again
-                    radio = e.other;
-                }
-            }
-        }
+    /// Built on the reusable `retry_until` combinator instead of hand-rolling the
+    /// "retry a fallible transition, recovering the radio from the error" loop.
+    async fn try_enter_standby_forever(radio: Radio<Uninitialized>) -> Radio<Standby> {
+        retry_until(
+            radio,
+            |radio| async move { radio.standby().await },
+            BackoffPolicy::constant(Duration::ZERO),
+        )
+        .await
+        .unwrap_or_else(|_| unreachable!("BackoffPolicy::constant has no max_attempts"))
     }
 
     #[tokio::test]
@@ -254,12 +351,10 @@ mod tests {
     #[tokio::test]
     async fn test_timeout_standby() -> Result<()> {
         let radio = Radio::<Uninitialized>::new_init(2);
-        // Create our timeout future, we try for 5 seconds then give up
-        let timeout = async {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            anyhow::anyhow!("Timeout waiting for standby")
-        };
-        let _radio = try_enter_standby_until(radio, timeout).await?;
+        // Give the radio 5 seconds to get into standby before giving up. A plain
+        // deadline like this is exactly what with_timeout is for, instead of
+        // hand-building a sleep-then-error future to race with try_enter_standby_until.
+        let _radio = with_timeout(try_enter_standby_forever(radio), Duration::from_secs(5)).await?;
 
         Ok(())
     }
@@ -302,4 +397,93 @@ mod tests {
         );
         Ok(())
     }
+
+    async fn standby_radio() -> Radio<Standby> {
+        Radio::<Uninitialized>::new()
+            .standby()
+            .await
+            .unwrap_or_else(|_| unreachable!("init_count is 0, standby can't fail"))
+    }
+
+    #[tokio::test]
+    async fn test_configure_rejects_zero_frequency() -> Result<()> {
+        let radio = standby_radio().await;
+        let bad = ConfigureData {
+            frequency_hz: 0,
+            ..ConfigureData::default()
+        };
+        let err = match radio.configure(bad).await {
+            Err(e) => e,
+            Ok(_) => panic!("expected configure to reject a zero frequency"),
+        };
+        assert!(err.to_string().contains("frequency"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_configure_rejects_out_of_range_power() -> Result<()> {
+        let radio = standby_radio().await;
+        let bad = ConfigureData {
+            power_dbm: MAX_POWER_DBM + 1,
+            ..ConfigureData::default()
+        };
+        let err = match radio.configure(bad).await {
+            Err(e) => e,
+            Ok(_) => panic!("expected configure to reject out-of-range power"),
+        };
+        assert!(err.to_string().contains("power"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_configure_rejects_out_of_range_channel() -> Result<()> {
+        let radio = standby_radio().await;
+        let bad = ConfigureData {
+            channel: MAX_CHANNEL + 1,
+            ..ConfigureData::default()
+        };
+        let err = match radio.configure(bad).await {
+            Err(e) => e,
+            Ok(_) => panic!("expected configure to reject an out-of-range channel"),
+        };
+        assert!(err.to_string().contains("channel"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_channel() -> Result<()> {
+        let radio = standby_radio().await;
+        let mut radio = radio
+            .configure(ConfigureData::default())
+            .await?
+            .operate()
+            .await?;
+
+        radio.set_channel(5).await?;
+        assert_eq!(radio.state.config.channel, 5);
+
+        let err = radio.set_channel(MAX_CHANNEL + 1).await.unwrap_err();
+        assert!(err.to_string().contains("MAX_CHANNEL"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconfigure_after_standby_retains_last_config() -> Result<()> {
+        let radio = standby_radio().await;
+        let radio = radio
+            .configure(ConfigureData::default())
+            .await?
+            .operate()
+            .await?
+            .enter_standby()
+            .await;
+        assert_eq!(radio.state.last_config, Some(ConfigureData::default()));
+
+        let new_config = ConfigureData {
+            channel: 3,
+            ..ConfigureData::default()
+        };
+        let _configured_again = radio.configure(new_config).await?;
+        Ok(())
+    }
 }